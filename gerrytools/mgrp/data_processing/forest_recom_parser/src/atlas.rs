@@ -0,0 +1,120 @@
+//! Deserialization support for the atlas `districting` entries.
+//!
+//! Each `districting` record is a JSON object whose keys are themselves
+//! JSON-encoded arrays of location identifiers (`["Hennepin"]`,
+//! `["Hennepin", "Ward 3"]`, or deeper nestings for states that key
+//! precincts below additional levels). `LocationKey` parses that encoded
+//! key so the assignment loop can dispatch on a variant rather than on
+//! `Vec::len()`.
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::de::{self, Deserializer, Visitor};
+use serde::Deserialize;
+
+/// A parsed `districting` key, identifying the location(s) an assignment
+/// applies to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LocationKey {
+    County(String),
+    Precinct { county: String, precinct: String },
+    Nested(Vec<String>),
+}
+
+impl<'de> Deserialize<'de> for LocationKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct LocationKeyVisitor;
+
+        impl<'de> Visitor<'de> for LocationKeyVisitor {
+            type Value = LocationKey;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a JSON-encoded array of location identifiers")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<LocationKey, E>
+            where
+                E: de::Error,
+            {
+                let parts: Vec<String> = serde_json::from_str(value)
+                    .map_err(|e| de::Error::custom(format!("invalid districting key {:?}: {}", value, e)))?;
+
+                Ok(match parts.len() {
+                    1 => LocationKey::County(parts.into_iter().next().unwrap()),
+                    2 => {
+                        let mut parts = parts.into_iter();
+                        let county = parts.next().unwrap();
+                        let precinct = parts.next().unwrap();
+                        LocationKey::Precinct { county, precinct }
+                    }
+                    _ => LocationKey::Nested(parts),
+                })
+            }
+
+            fn visit_string<E>(self, value: String) -> Result<LocationKey, E>
+            where
+                E: de::Error,
+            {
+                self.visit_str(&value)
+            }
+        }
+
+        deserializer.deserialize_str(LocationKeyVisitor)
+    }
+}
+
+/// One `districting` entry: a map from parsed location key to the district
+/// that location was assigned to.
+#[derive(Debug, Deserialize)]
+pub struct DistrictingItem(pub HashMap<LocationKey, u64>);
+
+/// One line of an ensemble JSONL file: a single plan and its districting.
+#[derive(Debug, Deserialize)]
+pub struct JsonlRecord {
+    pub name: String,
+    pub weight: u32,
+    pub data: serde_json::Value,
+    pub districting: Vec<DistrictingItem>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_key(key: &str) -> LocationKey {
+        let mut map = serde_json::Map::new();
+        map.insert(key.to_string(), serde_json::json!(1));
+        let item: DistrictingItem = serde_json::from_value(serde_json::Value::Object(map)).unwrap();
+        item.0.into_keys().next().unwrap()
+    }
+
+    #[test]
+    fn parses_county_key() {
+        assert_eq!(parse_key("[\"Hennepin\"]"), LocationKey::County("Hennepin".to_string()));
+    }
+
+    #[test]
+    fn parses_precinct_key() {
+        assert_eq!(
+            parse_key("[\"Hennepin\", \"Ward 3\"]"),
+            LocationKey::Precinct { county: "Hennepin".to_string(), precinct: "Ward 3".to_string() },
+        );
+    }
+
+    #[test]
+    fn parses_nested_key() {
+        assert_eq!(
+            parse_key("[\"Hennepin\", \"Ward 3\", \"Precinct 2\"]"),
+            LocationKey::Nested(vec!["Hennepin".to_string(), "Ward 3".to_string(), "Precinct 2".to_string()]),
+        );
+    }
+
+    #[test]
+    fn rejects_non_json_key() {
+        let result: Result<DistrictingItem, _> = serde_json::from_str("{\"not json\": 1}");
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,207 @@
+//! Byte-offset index for random access into ensemble JSONL files.
+//!
+//! `build_index` makes a single sequential pass recording each plan's byte
+//! offset, name, and weight to a sidecar file; `PlanReader` then seeks
+//! straight to a given plan number or name and decodes just that one line,
+//! which matters once an ensemble file runs to multiple gigabytes.
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::error::Error;
+
+use serde::{Deserialize, Serialize};
+
+use crate::atlas::JsonlRecord;
+
+/// Byte offset and decoded identity of one plan line in an ensemble file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanIndexEntry {
+    pub offset: u64,
+    pub name: String,
+    pub weight: u32,
+}
+
+/// The full sidecar index for one ensemble file, plus the source file's byte
+/// length at the time it was built, so a stale sidecar from a regenerated or
+/// truncated ensemble file can be detected and rebuilt instead of trusted.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PlanIndex {
+    pub entries: Vec<PlanIndexEntry>,
+    pub source_len: u64,
+}
+
+/// Returns the sidecar index path for an ensemble file.
+pub fn sidecar_path(jsonl_path: &str) -> String {
+    format!("{}.idx.json", jsonl_path)
+}
+
+/// Scans `jsonl_path` once, recording the byte offset of every line that
+/// decodes as a plan record. Lines that don't (a metadata/header prefix,
+/// however long) are skipped rather than assumed to be a fixed count.
+pub fn build_index(jsonl_path: &str) -> Result<PlanIndex, Box<dyn Error>> {
+    let file = File::open(jsonl_path)?;
+    let mut reader = BufReader::new(file);
+
+    let mut entries = Vec::new();
+    let mut offset = 0u64;
+    loop {
+        let mut line = String::new();
+        let bytes_read = reader.read_line(&mut line)?;
+        if bytes_read == 0 {
+            break;
+        }
+
+        if let Ok(record) = serde_json::from_str::<JsonlRecord>(line.trim_end()) {
+            entries.push(PlanIndexEntry {
+                offset,
+                name: record.name,
+                weight: record.weight,
+            });
+        }
+
+        offset += bytes_read as u64;
+    }
+
+    Ok(PlanIndex { entries, source_len: offset })
+}
+
+/// Writes an index to its sidecar file as JSON.
+pub fn save_index(index: &PlanIndex, sidecar_path: &str) -> Result<(), Box<dyn Error>> {
+    let file = File::create(sidecar_path)?;
+    serde_json::to_writer(file, index)?;
+    Ok(())
+}
+
+/// Loads a previously built sidecar index.
+pub fn load_index(sidecar_path: &str) -> Result<PlanIndex, Box<dyn Error>> {
+    let file = File::open(sidecar_path)?;
+    Ok(serde_json::from_reader(file)?)
+}
+
+/// Random-access reader over an ensemble JSONL file backed by a `PlanIndex`.
+pub struct PlanReader {
+    file: File,
+    index: PlanIndex,
+}
+
+impl PlanReader {
+    /// Opens `jsonl_path`, reusing its sidecar index if present and still
+    /// matching the file's current length, or (re)building and saving one
+    /// otherwise. The length check catches the common ways a sidecar goes
+    /// stale — a rerun regenerating the ensemble file, or a truncated/appended
+    /// one — without needing a full rescan just to open the file.
+    pub fn open(jsonl_path: &str) -> Result<Self, Box<dyn Error>> {
+        let current_len = File::open(jsonl_path)?.metadata()?.len();
+
+        let sidecar = sidecar_path(jsonl_path);
+        let index = match load_index(&sidecar) {
+            Ok(index) if index.source_len == current_len => index,
+            _ => {
+                let index = build_index(jsonl_path)?;
+                save_index(&index, &sidecar)?;
+                index
+            }
+        };
+        Ok(PlanReader { file: File::open(jsonl_path)?, index })
+    }
+
+    pub fn len(&self) -> usize {
+        self.index.entries.len()
+    }
+
+    /// Indexed `(offset, name, weight)` metadata for every plan, without
+    /// decoding any plan bodies.
+    pub fn entries(&self) -> &[PlanIndexEntry] {
+        &self.index.entries
+    }
+
+    /// Seeks to and decodes the `n`th plan in the ensemble.
+    pub fn read_plan(&mut self, n: usize) -> Result<JsonlRecord, Box<dyn Error>> {
+        let entry = self.index.entries.get(n)
+            .ok_or_else(|| format!("no plan at index {}", n))?;
+        self.file.seek(SeekFrom::Start(entry.offset))?;
+
+        let mut line = String::new();
+        BufReader::new(&self.file).read_line(&mut line)?;
+        Ok(serde_json::from_str(line.trim_end())?)
+    }
+
+    /// Seeks to and decodes the first plan with the given name.
+    pub fn read_plan_by_name(&mut self, name: &str) -> Result<JsonlRecord, Box<dyn Error>> {
+        let n = self.index.entries.iter().position(|entry| entry.name == name)
+            .ok_or_else(|| format!("no plan named {:?}", name))?;
+        self.read_plan(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::temp_path;
+    use std::io::Write;
+    use std::path::Path;
+
+    fn write_ensemble(path: &str) {
+        let mut file = File::create(path).unwrap();
+        writeln!(file, "{{\"run params\": \"not a plan\"}}").unwrap();
+        writeln!(file, "{{\"name\": \"plan_0\", \"weight\": 1, \"data\": null, \"districting\": []}}").unwrap();
+        writeln!(file, "{{\"name\": \"plan_1\", \"weight\": 9, \"data\": null, \"districting\": []}}").unwrap();
+        writeln!(file, "{{\"name\": \"plan_2\", \"weight\": 3, \"data\": null, \"districting\": []}}").unwrap();
+    }
+
+    #[test]
+    fn build_index_skips_metadata_prefix_and_finds_plans_by_position_and_name() {
+        let jsonl_path = temp_path("ensemble.jsonl");
+        write_ensemble(&jsonl_path);
+
+        let mut reader = PlanReader::open(&jsonl_path).unwrap();
+        assert_eq!(reader.len(), 3);
+
+        let plan = reader.read_plan(1).unwrap();
+        assert_eq!(plan.name, "plan_1");
+        assert_eq!(plan.weight, 9);
+
+        let plan = reader.read_plan_by_name("plan_2").unwrap();
+        assert_eq!(plan.name, "plan_2");
+        assert_eq!(plan.weight, 3);
+
+        assert!(reader.read_plan_by_name("not_a_plan").is_err());
+
+        std::fs::remove_file(&jsonl_path).ok();
+        std::fs::remove_file(sidecar_path(&jsonl_path)).ok();
+    }
+
+    #[test]
+    fn open_reuses_a_sidecar_index_built_by_an_earlier_run() {
+        let jsonl_path = temp_path("ensemble_reuse.jsonl");
+        write_ensemble(&jsonl_path);
+
+        PlanReader::open(&jsonl_path).unwrap();
+        assert!(Path::new(&sidecar_path(&jsonl_path)).exists());
+
+        let mut reader = PlanReader::open(&jsonl_path).unwrap();
+        assert_eq!(reader.read_plan(0).unwrap().name, "plan_0");
+
+        std::fs::remove_file(&jsonl_path).ok();
+        std::fs::remove_file(sidecar_path(&jsonl_path)).ok();
+    }
+
+    #[test]
+    fn open_rebuilds_the_index_when_the_ensemble_file_has_changed_size() {
+        let jsonl_path = temp_path("ensemble_stale.jsonl");
+        write_ensemble(&jsonl_path);
+
+        PlanReader::open(&jsonl_path).unwrap();
+
+        // Regenerate the ensemble file with a new plan appended, leaving the
+        // stale sidecar (with the old source_len) in place.
+        let mut file = File::options().append(true).open(&jsonl_path).unwrap();
+        writeln!(file, "{{\"name\": \"plan_3\", \"weight\": 5, \"data\": null, \"districting\": []}}").unwrap();
+
+        let mut reader = PlanReader::open(&jsonl_path).unwrap();
+        assert_eq!(reader.len(), 4);
+        assert_eq!(reader.read_plan_by_name("plan_3").unwrap().weight, 5);
+
+        std::fs::remove_file(&jsonl_path).ok();
+        std::fs::remove_file(sidecar_path(&jsonl_path)).ok();
+    }
+}
@@ -2,44 +2,72 @@ extern crate csv;
 extern crate serde;
 extern crate serde_json;
 
+mod atlas;
+mod index;
+mod metrics;
+mod output;
+mod schema;
+mod stv;
+mod tally;
+#[cfg(test)]
+mod test_support;
+
 use std::fs::File;
-use serde::Deserialize;
 use std::error::Error;
 use std::io::{BufRead, BufReader};
 use std::collections::HashMap;
 
-#[derive(Debug, Deserialize)]
-struct District {
-    county_name: String, 
-    precinct_name: String,
-    election_1: u64,
-    election_2: u64,
-    assignment: u64,
-}
-
-
-#[derive(Debug, Deserialize)]
-struct DistrictingItem(serde_json::Map<String, serde_json::Value>);
-
-#[derive(Debug, Deserialize)]
-struct JsonlRecord {
-    name: String,
-    weight: u32,
-    data: serde_json::Value,
-    districting: Vec<DistrictingItem>,
+use atlas::{JsonlRecord, LocationKey};
+use index::PlanReader;
+use output::{OutputFormat, PlanRecord, ResultsWriter};
+use schema::District;
+use tally::ElectionTally;
+
+/// Number of district buckets to allocate per election tally.
+const NUM_DISTRICTS: usize = 100;
+
+/// How often (in plan steps) to recompute tallies from scratch and assert
+/// they match the incrementally maintained ones.
+const SELF_CHECK_INTERVAL: usize = 50;
+
+/// Resolves the district indices a `districting` key applies to.
+fn affected_indices(
+    key: &LocationKey,
+    district_list: &[District],
+    district_map_2: &HashMap<(String, String), usize>,
+) -> Vec<usize> {
+    match key {
+        LocationKey::County(county) => district_list.iter().enumerate()
+            .filter(|(_, dist)| &dist.county_name == county)
+            .map(|(index, _)| index)
+            .collect(),
+        LocationKey::Precinct { county, precinct } => district_map_2
+            .get(&(county.clone(), precinct.clone()))
+            .copied()
+            .into_iter()
+            .collect(),
+        LocationKey::Nested(parts) => {
+            eprintln!("unsupported nested districting key {:?}, skipping", parts);
+            Vec::new()
+        }
+    }
 }
 
-
 fn main() -> Result<(), Box<dyn Error>> {
     let file_path = "../main_data.csv";
     let file = File::open(file_path)?;
 
-    let mut district_list = Vec::<District>::new();
-
     let mut reader = csv::Reader::from_reader(file);
-    for result in reader.deserialize() {
-        let district: District = result?;
-        district_list.push(district);
+    let headers = reader.headers()?.clone();
+    let elections = schema::detect_election_pairs(&headers);
+    if elections.is_empty() {
+        return Err(format!("no _D/_R election column pairs found in {}'s header", file_path).into());
+    }
+
+    let mut district_list = Vec::<District>::new();
+    for result in reader.records() {
+        let record = result?;
+        district_list.push(schema::parse_district(&headers, &record, &elections)?);
     }
 
     let district_map_2: HashMap<(String, String), usize> = district_list.iter().enumerate()
@@ -50,63 +78,133 @@ fn main() -> Result<(), Box<dyn Error>> {
     let jsonl_file = File::open(jsonl_path)?;
     let jsonl_reader = BufReader::new(jsonl_file);
 
-    let mut wins_counter = vec![0;20];
-    let mut step_counter = 0;
+    // Win-count histogram per election, plus a running per-election seat sum
+    // for the averaged-over-elections summary.
+    let mut wins_counter: HashMap<String, Vec<u32>> = elections.iter()
+        .map(|e| (e.name.clone(), vec![0; 20]))
+        .collect();
+    let mut seats_sum: HashMap<String, u64> = elections.iter()
+        .map(|e| (e.name.clone(), 0))
+        .collect();
 
-    for (index, line_result) in jsonl_reader.lines().enumerate() {
-        if index < 3 {
-            continue;
-        }
+    // Persistent tallies, one per election, seeded from the districts'
+    // initial assignments so every later step only has to patch in the
+    // precincts a `districting` record actually moved.
+    let mut tallies: HashMap<String, ElectionTally> = elections.iter()
+        .map(|e| (e.name.clone(), ElectionTally::from_scratch(&district_list, &e.name, NUM_DISTRICTS)))
+        .collect();
 
+    let mut step_counter = 0;
+
+    // `--csv` picks CSV output; otherwise results are written as newline-
+    // delimited JSON.
+    let output_format = if std::env::args().any(|arg| arg == "--csv") {
+        OutputFormat::Csv
+    } else {
+        OutputFormat::Json
+    };
+    let results_path = match output_format {
+        OutputFormat::Csv => "../output/ensemble_results.csv",
+        OutputFormat::Json => "../output/ensemble_results.json",
+    };
+    let mut results_writer = ResultsWriter::new(results_path, output_format)?;
+
+    // Ranked ballots for any multi-member districts in this state; most
+    // plurality-only ensembles won't have a BLT directory at all.
+    let precinct_ballots = stv::import_precinct_ballots_if_present("../ballots")?;
+
+    for line_result in jsonl_reader.lines() {
         let line = line_result?;
-        let record: JsonlRecord = serde_json::from_str(&line)?;
-        
+        // The file opens with a metadata prefix (run parameters, etc.) that
+        // doesn't decode as a plan record; skip it rather than assuming a
+        // fixed number of header lines.
+        let record: JsonlRecord = match serde_json::from_str(&line) {
+            Ok(record) => record,
+            Err(_) => continue,
+        };
+
         for item in record.districting {
-            for (key, value) in &item.0 {
-                let assign_districts: Vec<String> = key.trim_start_matches('[')
-                    .trim_end_matches(']')
-                    .split("\", \"")
-                    .map(|s| s.replace("\"", ""))
-                    .collect();
-
-                match assign_districts.len() {
-                    1 => {
-                        let target_county = &assign_districts[0];
-                        for dist in district_list.iter_mut() {
-                            if &dist.county_name == target_county {
-                                dist.assignment = value.as_u64().unwrap_or(0);
-                            }
-                        }
+            for (key, &new_assignment) in &item.0 {
+                let new_assignment = new_assignment as usize;
+                for idx in affected_indices(key, &district_list, &district_map_2) {
+                    let old_assignment = district_list[idx].assignment as usize;
+                    if old_assignment == new_assignment {
+                        continue;
                     }
-                    2 => {
-                        if let Some(index) = district_map_2.get(&(assign_districts[0].clone(), assign_districts[1].clone())) {
-                            district_list[*index].assignment = value.as_u64().unwrap_or(0);
+
+                    for election in &elections {
+                        if let Some(votes) = district_list[idx].votes.get(&election.name) {
+                            tallies.get_mut(&election.name).unwrap()
+                                .move_precinct(old_assignment, new_assignment, votes[0], votes[1]);
                         }
                     }
-                    _ => {}
+                    district_list[idx].assignment = new_assignment as u64;
                 }
             }
         }
 
-        let mut counts_election_1 = vec![0;100];
-        let mut counts_election_2 = vec![0;100];
-        
-        for dist in &district_list {
-            counts_election_1[dist.assignment as usize] += dist.election_1;
-            counts_election_2[dist.assignment as usize] += dist.election_2;
+        step_counter += 1;
+
+        if step_counter % SELF_CHECK_INTERVAL == 0 {
+            for election in &elections {
+                tallies[&election.name].assert_consistent(&district_list, &election.name);
+            }
         }
 
-        let num_won: u32 = counts_election_1.into_iter().zip(counts_election_2.into_iter())
-                            .collect::<Vec<_>>()
-                            .into_iter()
-                            .map(|(x,y)| if x > y {1u32} else {0u32})
-                            .collect::<Vec<_>>()
-                            .into_iter()
-                            .sum();
-        wins_counter[num_won as usize] += 1;
-        step_counter += 1;
-        println!("{:?}",wins_counter);
-        println!("Number won {}, step {}, running avg {}", num_won, step_counter, wins_counter.iter().enumerate().map(|(a,&b)| a as u32*b as u32).collect::<Vec<u32>>().iter().sum::<u32>() as f64 /step_counter as f64);
+        for election in &elections {
+            let tally = &tallies[&election.name];
+            let plan_metrics = metrics::compute_metrics(&tally.counts_a, &tally.counts_b);
+            let num_won = plan_metrics.num_won;
+
+            let histogram = wins_counter.get_mut(&election.name).unwrap();
+            histogram[num_won as usize] += 1;
+            *seats_sum.get_mut(&election.name).unwrap() += num_won as u64;
+
+            results_writer.write_record(&PlanRecord {
+                step: step_counter,
+                plan_name: record.name.clone(),
+                weight: record.weight,
+                election: election.name.clone(),
+                num_won,
+                efficiency_gap: plan_metrics.efficiency_gap,
+                mean_median: plan_metrics.mean_median,
+                partisan_bias: plan_metrics.partisan_bias,
+                seats_votes: serde_json::to_string(&plan_metrics.seats_votes)?,
+            })?;
+        }
+
+        if !precinct_ballots.is_empty() {
+            let stv_results = stv::tabulate_by_district(&precinct_ballots, &district_list);
+            for (district, result) in &stv_results {
+                println!("[STV] step {}, district {} ({}): elected {:?}", step_counter, district, result.title, result.elected);
+            }
+        }
+    }
+
+    results_writer.finish(&wins_counter)?;
+
+    for election in &elections {
+        let avg = seats_sum[&election.name] as f64 / step_counter as f64;
+        println!(
+            "[{}] histogram {:?}, running avg seats won {}",
+            election.name, wins_counter[&election.name], avg
+        );
+    }
+
+    let avg_over_elections = elections.iter()
+        .map(|e| seats_sum[&e.name] as f64 / step_counter as f64)
+        .sum::<f64>() / elections.len() as f64;
+    println!("avg seats won, averaged over {} elections: {}", elections.len(), avg_over_elections);
+
+    // Random access into the highest-weight plan without replaying the
+    // chain: the index was built lazily on open and is reused on the next
+    // run via its sidecar file.
+    let mut plan_reader = PlanReader::open(jsonl_path)?;
+    println!("indexed {} plans for random access", plan_reader.len());
+    if let Some(entry) = plan_reader.entries().iter().max_by_key(|entry| entry.weight) {
+        let heaviest_name = entry.name.clone();
+        let heaviest = plan_reader.read_plan_by_name(&heaviest_name)?;
+        println!("heaviest plan: {:?} (weight {})", heaviest.name, heaviest.weight);
     }
 
     Ok(())
@@ -0,0 +1,181 @@
+//! Partisan-fairness metrics computed from per-district vote tallies.
+//!
+//! `compute_metrics` takes the same two inputs the old `main()` used to
+//! reduce to a single scalar (`counts_election_1`, `counts_election_2`), but
+//! instead of collapsing them to `num_won` it reports the standard ensemble
+//! diagnostics so a whole distribution of plans can be studied rather than
+//! just the seat count.
+
+/// Party-1 vote shares, seat counts, and fairness scores for a single plan.
+#[derive(Debug, Clone, Default)]
+pub struct PlanMetrics {
+    pub num_won: u32,
+    pub efficiency_gap: f64,
+    pub mean_median: f64,
+    pub partisan_bias: f64,
+    pub seats_votes: Vec<(f64, f64)>,
+}
+
+/// Party-1 share of the two-party vote in a district, or `None` if the
+/// district has no population (both counts zero).
+fn district_share(e1: u64, e2: u64) -> Option<f64> {
+    let total = e1 + e2;
+    if total == 0 {
+        None
+    } else {
+        Some(e1 as f64 / total as f64)
+    }
+}
+
+/// Computes the efficiency gap, mean-median difference, partisan bias,
+/// seats-votes curve, and seat count for party 1 from per-district
+/// `counts_election_1`/`counts_election_2` tallies.
+pub fn compute_metrics(counts_election_1: &[u64], counts_election_2: &[u64]) -> PlanMetrics {
+    let shares: Vec<f64> = counts_election_1
+        .iter()
+        .zip(counts_election_2.iter())
+        .filter_map(|(&e1, &e2)| district_share(e1, e2))
+        .collect();
+
+    PlanMetrics {
+        num_won: count_seats(&shares, 0.5),
+        efficiency_gap: efficiency_gap(counts_election_1, counts_election_2),
+        mean_median: mean_median(&shares),
+        partisan_bias: partisan_bias(&shares),
+        seats_votes: seats_votes_curve(&shares),
+    }
+}
+
+/// Counts districts where party 1's share exceeds `threshold`.
+fn count_seats(shares: &[f64], threshold: f64) -> u32 {
+    shares.iter().filter(|&&s| s > threshold).count() as u32
+}
+
+/// Efficiency gap: `(wasted_2 - wasted_1) / total_votes` across all districts
+/// with population. A district's loser wastes every vote it cast; its winner
+/// wastes every vote above the 50%+1 needed to win.
+fn efficiency_gap(counts_election_1: &[u64], counts_election_2: &[u64]) -> f64 {
+    let mut wasted_1 = 0f64;
+    let mut wasted_2 = 0f64;
+    let mut total_votes = 0f64;
+
+    for (&e1, &e2) in counts_election_1.iter().zip(counts_election_2.iter()) {
+        let total = e1 + e2;
+        if total == 0 {
+            continue;
+        }
+        let (e1, e2, total) = (e1 as f64, e2 as f64, total as f64);
+        let win_threshold = total / 2.0 + 1.0;
+
+        if e1 > e2 {
+            wasted_1 += e1 - win_threshold;
+            wasted_2 += e2;
+        } else {
+            wasted_2 += e2 - win_threshold;
+            wasted_1 += e1;
+        }
+        total_votes += total;
+    }
+
+    if total_votes == 0.0 {
+        0.0
+    } else {
+        (wasted_2 - wasted_1) / total_votes
+    }
+}
+
+/// Mean-median difference: `median(shares) - mean(shares)`.
+fn mean_median(shares: &[f64]) -> f64 {
+    if shares.is_empty() {
+        return 0.0;
+    }
+    let mut sorted = shares.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let median = if sorted.len().is_multiple_of(2) {
+        let mid = sorted.len() / 2;
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[sorted.len() / 2]
+    };
+    let mean = shares.iter().sum::<f64>() / shares.len() as f64;
+    median - mean
+}
+
+/// Partisan bias: shift every district share by the amount needed to bring
+/// the statewide share to exactly 50%, then report how far the resulting
+/// seat share is from 50%.
+fn partisan_bias(shares: &[f64]) -> f64 {
+    if shares.is_empty() {
+        return 0.0;
+    }
+    let statewide = shares.iter().sum::<f64>() / shares.len() as f64;
+    let shift = 0.5 - statewide;
+    let shifted_seats = count_seats(&shares.iter().map(|s| s + shift).collect::<Vec<_>>(), 0.5);
+    (shifted_seats as f64 / shares.len() as f64) - 0.5
+}
+
+/// Seats-votes curve: for a grid of hypothetical statewide vote shares,
+/// the seat share party 1 would win under a uniform swing from its actual
+/// shares. Returned as `(vote_share, seat_share)` pairs.
+fn seats_votes_curve(shares: &[f64]) -> Vec<(f64, f64)> {
+    if shares.is_empty() {
+        return Vec::new();
+    }
+    let statewide = shares.iter().sum::<f64>() / shares.len() as f64;
+    let mut curve = Vec::new();
+    let mut vote_share = 0.01;
+    while vote_share < 1.0 {
+        let shift = vote_share - statewide;
+        let seats = count_seats(&shares.iter().map(|s| s + shift).collect::<Vec<_>>(), 0.5);
+        curve.push((vote_share, seats as f64 / shares.len() as f64));
+        vote_share += 0.01;
+    }
+    curve
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "expected {}, got {}", expected, actual);
+    }
+
+    #[test]
+    fn compute_metrics_matches_hand_worked_example() {
+        // Three 100-vote districts with shares 0.7, 0.4, 0.1.
+        let counts_election_1 = vec![70, 40, 10];
+        let counts_election_2 = vec![30, 60, 90];
+
+        let result = compute_metrics(&counts_election_1, &counts_election_2);
+
+        assert_eq!(result.num_won, 1);
+        assert_close(result.efficiency_gap, 0.03);
+        assert_close(result.mean_median, 0.0);
+        assert_close(result.partisan_bias, 1.0 / 3.0 - 0.5);
+    }
+
+    #[test]
+    fn compute_metrics_ignores_empty_districts() {
+        let counts_election_1 = vec![60, 0];
+        let counts_election_2 = vec![40, 0];
+
+        let result = compute_metrics(&counts_election_1, &counts_election_2);
+
+        assert_eq!(result.num_won, 1);
+        assert_close(result.mean_median, 0.0);
+    }
+
+    #[test]
+    fn seats_votes_curve_is_monotonic_in_vote_share() {
+        let counts_election_1 = vec![60, 40];
+        let counts_election_2 = vec![40, 60];
+
+        let result = compute_metrics(&counts_election_1, &counts_election_2);
+
+        assert!(!result.seats_votes.is_empty());
+        for window in result.seats_votes.windows(2) {
+            assert!(window[1].1 >= window[0].1, "seat share should not decrease as vote share rises");
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! Structured result output.
+//!
+//! `ResultsWriter` streams one record per plan per election to a file in
+//! either CSV or JSON, then writes the final win-count histogram (appended
+//! to the same file in JSON mode, to a sidecar file in CSV mode), so the
+//! output can be loaded into a notebook or other tooling.
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::File;
+use std::io::Write;
+
+use serde::Serialize;
+
+/// Output file format for ensemble results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Json,
+    Csv,
+}
+
+/// One plan's metrics for one election.
+#[derive(Debug, Serialize)]
+pub struct PlanRecord {
+    pub step: usize,
+    pub plan_name: String,
+    pub weight: u32,
+    pub election: String,
+    pub num_won: u32,
+    pub efficiency_gap: f64,
+    pub mean_median: f64,
+    pub partisan_bias: f64,
+    /// The seats-votes curve (`metrics::PlanMetrics::seats_votes`) as a
+    /// JSON-encoded array of `(vote_share, seat_share)` pairs, kept as a
+    /// string so it serializes as a single scalar column in CSV mode too.
+    pub seats_votes: String,
+}
+
+/// One row of the final win-count histogram, emitted in CSV mode.
+#[derive(Debug, Serialize)]
+struct HistogramRow {
+    election: String,
+    num_won: usize,
+    plans: u32,
+}
+
+enum Sink {
+    Csv(Box<csv::Writer<File>>),
+    Json(File),
+}
+
+/// Derives the sidecar path the CSV histogram is written to, since it has a
+/// different shape than the `PlanRecord` rows and can't share a `csv::Writer`
+/// with them (the crate locks the field count to the first row serialized).
+fn histogram_sidecar_path(path: &str) -> String {
+    format!("{}.histogram.csv", path)
+}
+
+/// Streams `PlanRecord`s to a file, then writes the final histogram (see
+/// `finish`).
+pub struct ResultsWriter {
+    sink: Sink,
+    histogram_path: Option<String>,
+}
+
+impl ResultsWriter {
+    pub fn new(path: &str, format: OutputFormat) -> Result<Self, Box<dyn Error>> {
+        let file = File::create(path)?;
+        let (sink, histogram_path) = match format {
+            OutputFormat::Csv => (
+                Sink::Csv(Box::new(csv::Writer::from_writer(file))),
+                Some(histogram_sidecar_path(path)),
+            ),
+            OutputFormat::Json => (Sink::Json(file), None),
+        };
+        Ok(ResultsWriter { sink, histogram_path })
+    }
+
+    /// Writes one per-plan-per-election record.
+    pub fn write_record(&mut self, record: &PlanRecord) -> Result<(), Box<dyn Error>> {
+        match &mut self.sink {
+            Sink::Csv(writer) => writer.serialize(record)?,
+            Sink::Json(file) => {
+                serde_json::to_writer(&mut *file, record)?;
+                writeln!(file)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Appends the final per-election win-count histogram and flushes. In
+    /// CSV mode the histogram goes to its own sidecar file, since its rows
+    /// have a different shape than the `PlanRecord` rows already written.
+    pub fn finish(mut self, histograms: &HashMap<String, Vec<u32>>) -> Result<(), Box<dyn Error>> {
+        match &mut self.sink {
+            Sink::Csv(writer) => {
+                writer.flush()?;
+
+                let histogram_path = self.histogram_path.as_ref()
+                    .expect("histogram_path is always set in CSV mode");
+                let mut histogram_writer = csv::Writer::from_path(histogram_path)?;
+                for (election, histogram) in histograms {
+                    for (num_won, &plans) in histogram.iter().enumerate() {
+                        if plans > 0 {
+                            histogram_writer.serialize(HistogramRow {
+                                election: election.clone(),
+                                num_won,
+                                plans,
+                            })?;
+                        }
+                    }
+                }
+                histogram_writer.flush()?;
+            }
+            Sink::Json(file) => {
+                serde_json::to_writer(&mut *file, histograms)?;
+                writeln!(file)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::temp_path;
+    use std::io::Read;
+
+    #[test]
+    fn csv_mode_writes_records_and_histogram_without_field_count_conflict() {
+        let results_path = temp_path("results.csv");
+        let mut writer = ResultsWriter::new(&results_path, OutputFormat::Csv).unwrap();
+
+        writer.write_record(&PlanRecord {
+            step: 1,
+            plan_name: "plan_a".to_string(),
+            weight: 1,
+            election: "USH".to_string(),
+            num_won: 3,
+            efficiency_gap: 0.05,
+            mean_median: 0.01,
+            partisan_bias: 0.02,
+            seats_votes: "[]".to_string(),
+        }).unwrap();
+
+        let mut histograms = HashMap::new();
+        histograms.insert("USH".to_string(), vec![0, 0, 0, 1]);
+
+        writer.finish(&histograms).unwrap();
+
+        let mut results_contents = String::new();
+        File::open(&results_path).unwrap().read_to_string(&mut results_contents).unwrap();
+        assert!(results_contents.contains("plan_a"));
+
+        let histogram_path = histogram_sidecar_path(&results_path);
+        let mut histogram_contents = String::new();
+        File::open(&histogram_path).unwrap().read_to_string(&mut histogram_contents).unwrap();
+        assert!(histogram_contents.contains("USH"));
+        assert!(histogram_contents.contains('3'));
+
+        std::fs::remove_file(&results_path).ok();
+        std::fs::remove_file(&histogram_path).ok();
+    }
+}
@@ -0,0 +1,109 @@
+//! Dynamic election schema for precinct CSVs.
+//!
+//! Precinct files carry an arbitrary ensemble of statewide contests rather
+//! than a fixed `election_1`/`election_2` pair, so instead of a derived
+//! `Deserialize` struct we read the CSV header ourselves and pair up columns
+//! into elections by a configurable two-party column-suffix convention
+//! (e.g. `USH20_D`/`USH20_R`).
+use std::collections::HashMap;
+use std::error::Error;
+
+/// The column-name suffixes that mark a party-A/party-B pair of columns
+/// belonging to the same election, e.g. `USH20_D`/`USH20_R`.
+pub const PARTY_A_SUFFIX: &str = "_D";
+pub const PARTY_B_SUFFIX: &str = "_R";
+
+/// One configured election: a display name plus the two CSV columns that
+/// hold its party-A and party-B vote counts.
+#[derive(Debug, Clone)]
+pub struct ElectionPair {
+    pub name: String,
+    pub party_a_column: String,
+    pub party_b_column: String,
+}
+
+/// A single precinct's assignment plus its vote counts for every configured
+/// election, keyed by election name. Each value is `[party_a, party_b]`.
+#[derive(Debug)]
+pub struct District {
+    pub county_name: String,
+    pub precinct_name: String,
+    pub votes: HashMap<String, Vec<u64>>,
+    pub assignment: u64,
+}
+
+/// Scans the CSV header for `PARTY_A_SUFFIX`/`PARTY_B_SUFFIX` column pairs
+/// sharing a common prefix and returns one `ElectionPair` per match.
+pub fn detect_election_pairs(headers: &csv::StringRecord) -> Vec<ElectionPair> {
+    let mut pairs = Vec::new();
+    for column in headers.iter() {
+        if let Some(prefix) = column.strip_suffix(PARTY_A_SUFFIX) {
+            let party_b_column = format!("{}{}", prefix, PARTY_B_SUFFIX);
+            if headers.iter().any(|c| c == party_b_column) {
+                pairs.push(ElectionPair {
+                    name: prefix.to_string(),
+                    party_a_column: column.to_string(),
+                    party_b_column,
+                });
+            }
+        }
+    }
+    pairs
+}
+
+/// Parses one CSV row into a `District` using the column positions implied
+/// by `headers` and the configured `elections`.
+pub fn parse_district(
+    headers: &csv::StringRecord,
+    record: &csv::StringRecord,
+    elections: &[ElectionPair],
+) -> Result<District, Box<dyn Error>> {
+    let column_index = |name: &str| -> Option<usize> { headers.iter().position(|c| c == name) };
+    let field = |name: &str| -> Result<&str, Box<dyn Error>> {
+        let index = column_index(name).ok_or_else(|| format!("missing column {}", name))?;
+        record
+            .get(index)
+            .ok_or_else(|| format!("row missing field for column {}", name).into())
+    };
+
+    let county_name = field("county_name")?.to_string();
+    let precinct_name = field("precinct_name")?.to_string();
+    let assignment: u64 = field("assignment")?.parse()?;
+
+    let mut votes = HashMap::new();
+    for election in elections {
+        let party_a: u64 = field(&election.party_a_column)?.parse()?;
+        let party_b: u64 = field(&election.party_b_column)?.parse()?;
+        votes.insert(election.name.clone(), vec![party_a, party_b]);
+    }
+
+    Ok(District {
+        county_name,
+        precinct_name,
+        votes,
+        assignment,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_district_reads_the_csv_assignment_column_instead_of_defaulting_to_zero() {
+        let headers = csv::StringRecord::from(vec![
+            "county_name", "precinct_name", "assignment", "USH20_D", "USH20_R",
+        ]);
+        let record = csv::StringRecord::from(vec!["Hennepin", "Ward 3", "7", "100", "80"]);
+        let elections = vec![ElectionPair {
+            name: "USH20".to_string(),
+            party_a_column: "USH20_D".to_string(),
+            party_b_column: "USH20_R".to_string(),
+        }];
+
+        let district = parse_district(&headers, &record, &elections).unwrap();
+
+        assert_eq!(district.assignment, 7);
+        assert_eq!(district.votes["USH20"], vec![100, 80]);
+    }
+}
@@ -0,0 +1,310 @@
+//! Ranked-choice (STV) tabulation and BLT ballot import.
+//!
+//! Handles multi-member districts with ranked ballots, imported from the
+//! BLT format and tabulated by single transferable vote with Droop quotas
+//! and Gregory-method surplus transfer. The plurality win computation
+//! elsewhere in this crate only covers single-winner two-column contests.
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use crate::schema::District;
+
+/// One ranked ballot: a weight (ballots can be pre-aggregated by rank
+/// pattern) and the candidates in preference order, by index into the
+/// owning `BltElection`'s `candidates`.
+#[derive(Debug, Clone)]
+pub struct Ballot {
+    pub weight: f64,
+    pub preferences: Vec<usize>,
+}
+
+/// A ranked election as read from one BLT file: its candidate slate, seat
+/// count, and ballots.
+#[derive(Debug, Clone)]
+pub struct BltElection {
+    pub seats: usize,
+    pub candidates: Vec<String>,
+    pub title: String,
+    pub ballots: Vec<Ballot>,
+}
+
+fn strip_quotes(s: &str) -> String {
+    s.trim_matches('"').to_string()
+}
+
+/// Parses a single BLT file: header `candidates seats`, ballot lines of
+/// `weight rank1 rank2 ... 0`, a `0` terminator, then quoted candidate
+/// names and a quoted title.
+pub fn parse_blt(path: &str) -> Result<BltElection, Box<dyn Error>> {
+    let content = fs::read_to_string(path)?;
+    let mut lines = content.lines();
+
+    let header = lines.next().ok_or("empty BLT file")?;
+    let mut header_fields = header.split_whitespace();
+    let num_candidates: usize = header_fields.next().ok_or("BLT header missing candidate count")?.parse()?;
+    let seats: usize = header_fields.next().ok_or("BLT header missing seat count")?.parse()?;
+
+    let mut ballots = Vec::new();
+    for line in &mut lines {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let tokens: Vec<i64> = line.split_whitespace()
+            .map(|token| token.parse::<i64>())
+            .collect::<Result<_, _>>()?;
+        if tokens.as_slice() == [0] {
+            break;
+        }
+
+        let weight = *tokens.first().ok_or("BLT ballot line missing weight")? as f64;
+        let preferences = tokens[1..].iter()
+            .take_while(|&&candidate| candidate != 0)
+            .map(|&candidate| (candidate - 1) as usize)
+            .collect();
+        ballots.push(Ballot { weight, preferences });
+    }
+
+    let mut candidates = Vec::with_capacity(num_candidates);
+    for _ in 0..num_candidates {
+        let line = lines.next().ok_or("BLT file missing a candidate name")?;
+        candidates.push(strip_quotes(line.trim()));
+    }
+    let title = lines.next().map(|line| strip_quotes(line.trim())).unwrap_or_default();
+
+    Ok(BltElection { seats, candidates, title, ballots })
+}
+
+/// Imports every `*.blt` file in `dir` into per-precinct ranked ballot
+/// bundles, keyed the same way as `schema::District`'s `(county_name,
+/// precinct_name)`. Files are expected to be named `county__precinct.blt`.
+pub fn import_precinct_ballots(dir: &str) -> Result<HashMap<(String, String), BltElection>, Box<dyn Error>> {
+    let mut bundles = HashMap::new();
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("blt") {
+            continue;
+        }
+
+        let stem = path.file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or("non-UTF8 BLT file name")?;
+        let mut parts = stem.splitn(2, "__");
+        let county = parts.next().ok_or("BLT file name missing county")?.to_string();
+        let precinct = parts.next().ok_or("BLT file name missing precinct")?.to_string();
+
+        let path_str = path.to_str().ok_or("non-UTF8 BLT path")?;
+        bundles.insert((county, precinct), parse_blt(path_str)?);
+    }
+
+    Ok(bundles)
+}
+
+/// A candidate's standing in the count: still in the running, already
+/// elected, or eliminated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Continuing,
+    Elected,
+    Eliminated,
+}
+
+/// The candidates an STV count elected, in the order they met quota (or
+/// were carried over once only as many candidates as seats remained).
+#[derive(Debug, Clone)]
+pub struct StvResult {
+    pub title: String,
+    pub elected: Vec<String>,
+}
+
+/// Droop quota: `floor(valid_votes / (seats + 1)) + 1`.
+fn droop_quota(valid_votes: f64, seats: usize) -> f64 {
+    (valid_votes / (seats as f64 + 1.0)).floor() + 1.0
+}
+
+/// Runs single transferable vote over `election`: elects any candidate
+/// meeting the Droop quota and transfers their surplus proportionally to
+/// next preferences, eliminating the lowest candidate whenever nobody
+/// meets quota, until all seats are filled.
+pub fn run_stv(election: &BltElection) -> StvResult {
+    let num_candidates = election.candidates.len();
+    let mut status = vec![Status::Continuing; num_candidates];
+    let mut ballot_value: Vec<f64> = election.ballots.iter().map(|b| b.weight).collect();
+    let mut ballot_pointer: Vec<usize> = vec![0; election.ballots.len()];
+
+    let total_valid_votes: f64 = ballot_value.iter().sum();
+    let quota = droop_quota(total_valid_votes, election.seats);
+
+    let mut elected_order = Vec::new();
+
+    while elected_order.len() < election.seats {
+        let remaining_seats = election.seats - elected_order.len();
+        let continuing: Vec<usize> = (0..num_candidates)
+            .filter(|&c| status[c] == Status::Continuing)
+            .collect();
+        if continuing.len() <= remaining_seats {
+            for c in continuing {
+                status[c] = Status::Elected;
+                elected_order.push(c);
+            }
+            break;
+        }
+
+        // Advance each ballot to its current preference (the first
+        // continuing candidate from where it left off) and tally.
+        let mut tallies = vec![0f64; num_candidates];
+        let mut current_candidate: Vec<Option<usize>> = vec![None; election.ballots.len()];
+        for (i, ballot) in election.ballots.iter().enumerate() {
+            if ballot_value[i] <= 0.0 {
+                continue;
+            }
+            let mut pointer = ballot_pointer[i];
+            while pointer < ballot.preferences.len() && status[ballot.preferences[pointer]] != Status::Continuing {
+                pointer += 1;
+            }
+            ballot_pointer[i] = pointer;
+            if let Some(&candidate) = ballot.preferences.get(pointer) {
+                tallies[candidate] += ballot_value[i];
+                current_candidate[i] = Some(candidate);
+            }
+        }
+
+        let winner = continuing.iter().copied()
+            .filter(|&c| tallies[c] >= quota)
+            .max_by(|&a, &b| tallies[a].partial_cmp(&tallies[b]).unwrap());
+
+        if let Some(elected) = winner {
+            status[elected] = Status::Elected;
+            elected_order.push(elected);
+
+            let votes = tallies[elected];
+            let surplus = votes - quota;
+            let transfer_factor = if votes > 0.0 { surplus / votes } else { 0.0 };
+
+            for (i, &candidate) in current_candidate.iter().enumerate() {
+                if candidate == Some(elected) {
+                    ballot_value[i] *= transfer_factor;
+                    ballot_pointer[i] += 1;
+                }
+            }
+        } else {
+            let loser = continuing.iter().copied()
+                .min_by(|&a, &b| tallies[a].partial_cmp(&tallies[b]).unwrap())
+                .expect("continuing candidates is non-empty here");
+            status[loser] = Status::Eliminated;
+
+            for (i, &candidate) in current_candidate.iter().enumerate() {
+                if candidate == Some(loser) {
+                    ballot_pointer[i] += 1;
+                }
+            }
+        }
+    }
+
+    StvResult {
+        title: election.title.clone(),
+        elected: elected_order.into_iter().map(|c| election.candidates[c].clone()).collect(),
+    }
+}
+
+/// Groups imported precinct ballot bundles by district assignment and runs
+/// STV per district, so the ensemble analysis can report elected
+/// candidates for multi-member ranked districts alongside the plurality
+/// win counts. Districts with no ranked ballots assigned to them are
+/// omitted.
+pub fn tabulate_by_district(
+    precinct_ballots: &HashMap<(String, String), BltElection>,
+    district_list: &[District],
+) -> HashMap<u64, StvResult> {
+    let mut merged: HashMap<u64, BltElection> = HashMap::new();
+
+    for dist in district_list {
+        let key = (dist.county_name.clone(), dist.precinct_name.clone());
+        let Some(precinct_election) = precinct_ballots.get(&key) else {
+            continue;
+        };
+
+        match merged.get_mut(&dist.assignment) {
+            Some(existing) => {
+                if existing.candidates != precinct_election.candidates || existing.seats != precinct_election.seats {
+                    eprintln!(
+                        "precinct {:?} has a candidate slate that doesn't match district {}'s other precincts, skipping",
+                        key, dist.assignment,
+                    );
+                    continue;
+                }
+                existing.ballots.extend(precinct_election.ballots.clone());
+            }
+            None => {
+                merged.insert(dist.assignment, precinct_election.clone());
+            }
+        }
+    }
+
+    merged.iter()
+        .map(|(&district, election)| (district, run_stv(election)))
+        .collect()
+}
+
+/// Convenience wrapper: imports ballots from `dir` if it exists, otherwise
+/// reports no ranked-choice data for this ensemble (most plurality-only
+/// runs won't have a BLT directory at all).
+pub fn import_precinct_ballots_if_present(dir: &str) -> Result<HashMap<(String, String), BltElection>, Box<dyn Error>> {
+    if Path::new(dir).is_dir() {
+        import_precinct_ballots(dir)
+    } else {
+        Ok(HashMap::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ballot(weight: f64, preferences: &[usize]) -> Ballot {
+        Ballot { weight, preferences: preferences.to_vec() }
+    }
+
+    #[test]
+    fn run_stv_elects_by_quota_then_by_surplus_transfer() {
+        // 2 seats, 3 candidates, 30 ballots; quota = floor(30 / 3) + 1 = 11.
+        // Alice starts over quota and her surplus transfers to Carol, who
+        // then clears quota too, filling both seats without an elimination.
+        let election = BltElection {
+            seats: 2,
+            candidates: vec!["Alice".to_string(), "Bob".to_string(), "Carol".to_string()],
+            title: "Test Election".to_string(),
+            ballots: vec![
+                ballot(15.0, &[0, 2]),
+                ballot(8.0, &[1]),
+                ballot(7.0, &[2]),
+            ],
+        };
+
+        let result = run_stv(&election);
+        assert_eq!(result.elected, vec!["Alice".to_string(), "Carol".to_string()]);
+    }
+
+    #[test]
+    fn run_stv_eliminates_the_lowest_candidate_when_nobody_meets_quota() {
+        // 1 seat, 3 candidates, 10 ballots; quota = floor(10 / 2) + 1 = 6.
+        // Nobody meets quota on first count, so Carol (lowest) is eliminated
+        // and her ballot transfers to Bob, who then clears quota.
+        let election = BltElection {
+            seats: 1,
+            candidates: vec!["Alice".to_string(), "Bob".to_string(), "Carol".to_string()],
+            title: "Test Election".to_string(),
+            ballots: vec![
+                ballot(4.0, &[0]),
+                ballot(5.0, &[1]),
+                ballot(1.0, &[2, 1]),
+            ],
+        };
+
+        let result = run_stv(&election);
+        assert_eq!(result.elected, vec!["Bob".to_string()]);
+    }
+}
@@ -0,0 +1,161 @@
+//! Incremental per-election vote tallies.
+//!
+//! Each atlas `districting` record only lists the precincts whose
+//! assignment changed, so rebuilding `counts_a`/`counts_b` from every
+//! district on every step is wasted work on large states. `ElectionTally`
+//! keeps the running per-district totals and the count of districts party A
+//! currently leads, updated by moving one precinct's votes from its old
+//! district bucket to its new one.
+use crate::schema::District;
+
+/// Whether party A leads a district bucket: more votes than party B, and
+/// the district has population (mirrors `metrics::compute_metrics`'s
+/// treatment of empty districts).
+fn party_a_leads(votes_a: u64, votes_b: u64) -> bool {
+    votes_a + votes_b > 0 && votes_a > votes_b
+}
+
+/// Running vote tallies and seat count for a single election.
+#[derive(Debug, Clone)]
+pub struct ElectionTally {
+    pub counts_a: Vec<u64>,
+    pub counts_b: Vec<u64>,
+    pub districts_won: u32,
+}
+
+impl ElectionTally {
+    /// Builds a tally from scratch by scanning every district's current
+    /// assignment. Used both for initialization and for the periodic
+    /// correctness self-check.
+    pub fn from_scratch(district_list: &[District], election_name: &str, num_districts: usize) -> Self {
+        let mut counts_a = vec![0u64; num_districts];
+        let mut counts_b = vec![0u64; num_districts];
+
+        for dist in district_list {
+            if let Some(votes) = dist.votes.get(election_name) {
+                counts_a[dist.assignment as usize] += votes[0];
+                counts_b[dist.assignment as usize] += votes[1];
+            }
+        }
+
+        let districts_won = counts_a.iter().zip(counts_b.iter())
+            .filter(|&(&a, &b)| party_a_leads(a, b))
+            .count() as u32;
+
+        ElectionTally { counts_a, counts_b, districts_won }
+    }
+
+    /// Moves one precinct's `(votes_a, votes_b)` from `old_district` to
+    /// `new_district`, updating `districts_won` in O(1).
+    pub fn move_precinct(&mut self, old_district: usize, new_district: usize, votes_a: u64, votes_b: u64) {
+        if old_district == new_district {
+            return;
+        }
+
+        let was_won = party_a_leads(self.counts_a[old_district], self.counts_b[old_district]);
+        self.counts_a[old_district] -= votes_a;
+        self.counts_b[old_district] -= votes_b;
+        let now_won = party_a_leads(self.counts_a[old_district], self.counts_b[old_district]);
+        if was_won && !now_won {
+            self.districts_won -= 1;
+        } else if !was_won && now_won {
+            self.districts_won += 1;
+        }
+
+        let was_won = party_a_leads(self.counts_a[new_district], self.counts_b[new_district]);
+        self.counts_a[new_district] += votes_a;
+        self.counts_b[new_district] += votes_b;
+        let now_won = party_a_leads(self.counts_a[new_district], self.counts_b[new_district]);
+        if was_won && !now_won {
+            self.districts_won -= 1;
+        } else if !was_won && now_won {
+            self.districts_won += 1;
+        }
+    }
+
+    /// Recomputes from `district_list` and panics if the result disagrees
+    /// with the incrementally maintained tally.
+    pub fn assert_consistent(&self, district_list: &[District], election_name: &str) {
+        let num_districts = self.counts_a.len();
+        let fresh = ElectionTally::from_scratch(district_list, election_name, num_districts);
+        assert_eq!(self.counts_a, fresh.counts_a, "counts_a drifted for election {}", election_name);
+        assert_eq!(self.counts_b, fresh.counts_b, "counts_b drifted for election {}", election_name);
+        assert_eq!(self.districts_won, fresh.districts_won, "districts_won drifted for election {}", election_name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn district(county: &str, precinct: &str, votes_a: u64, votes_b: u64, assignment: u64) -> District {
+        let mut votes = HashMap::new();
+        votes.insert("X".to_string(), vec![votes_a, votes_b]);
+        District {
+            county_name: county.to_string(),
+            precinct_name: precinct.to_string(),
+            votes,
+            assignment,
+        }
+    }
+
+    #[test]
+    fn move_precinct_matches_from_scratch_recompute() {
+        let mut districts = vec![
+            district("A", "1", 60, 40, 0),
+            district("A", "2", 10, 20, 0),
+            district("B", "1", 5, 50, 1),
+        ];
+
+        let mut tally = ElectionTally::from_scratch(&districts, "X", 2);
+        assert_eq!(tally.counts_a, vec![70, 5]);
+        assert_eq!(tally.counts_b, vec![60, 50]);
+        assert_eq!(tally.districts_won, 1);
+
+        // Move "A"/"2" from district 0 into district 1.
+        tally.move_precinct(0, 1, 10, 20);
+        districts[1].assignment = 1;
+
+        assert_eq!(tally.counts_a, vec![60, 15]);
+        assert_eq!(tally.counts_b, vec![40, 70]);
+        tally.assert_consistent(&districts, "X");
+    }
+
+    #[test]
+    fn move_precinct_is_a_no_op_when_district_is_unchanged() {
+        let districts = vec![district("A", "1", 60, 40, 0)];
+        let mut tally = ElectionTally::from_scratch(&districts, "X", 1);
+        let before = tally.clone();
+
+        tally.move_precinct(0, 0, 60, 40);
+
+        assert_eq!(tally.counts_a, before.counts_a);
+        assert_eq!(tally.counts_b, before.counts_b);
+        assert_eq!(tally.districts_won, before.districts_won);
+    }
+
+    #[test]
+    fn move_precinct_can_flip_which_district_is_won() {
+        let mut districts = vec![
+            district("A", "1", 5, 5, 0),
+            district("A", "2", 80, 0, 0),
+            district("B", "1", 5, 50, 1),
+        ];
+        let mut tally = ElectionTally::from_scratch(&districts, "X", 2);
+        // Bucket 0: 85 vs 5 (A leads); bucket 1: 5 vs 50 (B leads).
+        assert_eq!(tally.counts_a, vec![85, 5]);
+        assert_eq!(tally.counts_b, vec![5, 50]);
+        assert_eq!(tally.districts_won, 1);
+
+        // Move the heavily-A precinct "A"/"2" out of bucket 0 into bucket 1.
+        tally.move_precinct(0, 1, 80, 0);
+        districts[1].assignment = 1;
+
+        // Bucket 0 is now a 5-5 tie (no longer won); bucket 1 flips to A.
+        assert_eq!(tally.counts_a, vec![5, 85]);
+        assert_eq!(tally.counts_b, vec![5, 50]);
+        assert_eq!(tally.districts_won, 1);
+        tally.assert_consistent(&districts, "X");
+    }
+}
@@ -0,0 +1,10 @@
+//! Shared fixtures for this crate's unit tests.
+#![cfg(test)]
+
+/// A process- and test-unique path under the system temp dir, so parallel
+/// test runs don't collide on the same file.
+pub fn temp_path(name: &str) -> String {
+    std::env::temp_dir()
+        .join(format!("forest_recom_parser_test_{}_{}", std::process::id(), name))
+        .to_str().unwrap().to_string()
+}